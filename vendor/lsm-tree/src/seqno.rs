@@ -7,9 +7,9 @@ use std::sync::OnceLock;
 use std::sync::{
     atomic::{
         AtomicU64,
-        Ordering::{Acquire, Release},
+        Ordering::{Acquire, Relaxed, Release},
     },
-    Arc,
+    Arc, Mutex,
 };
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
@@ -58,13 +58,16 @@ fn get_epoch_offset() -> (Instant, u128) {
 /// # Ok::<(), lsm_tree::Error>(())
 /// ```
 #[derive(Clone, Debug)]
-pub struct SequenceNumberCounter(Arc<AtomicU64>);
+pub struct SequenceNumberCounter {
+    counter: Arc<AtomicU64>,
+    diagnostics: Option<Arc<Diagnostics>>,
+}
 
 impl std::ops::Deref for SequenceNumberCounter {
     type Target = Arc<AtomicU64>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.counter
     }
 }
 
@@ -72,7 +75,35 @@ impl SequenceNumberCounter {
     /// Creates a new counter, setting it to some previous value
     #[must_use]
     pub fn new(prev: SeqNo) -> Self {
-        Self(Arc::new(AtomicU64::new(prev)))
+        Self {
+            counter: Arc::new(AtomicU64::new(prev)),
+            diagnostics: None,
+        }
+    }
+
+    /// Enables the clock-health diagnostics subsystem on this counter.
+    ///
+    /// Diagnostics are off by default — the scalar counters and the
+    /// recent-corrections ring are only touched when enabled, so [`next`] stays
+    /// near-zero overhead otherwise. The returned handle shares its diagnostics
+    /// with every clone.
+    ///
+    /// [`next`]: SequenceNumberCounter::next
+    #[must_use]
+    pub fn with_diagnostics(mut self) -> Self {
+        self.diagnostics = Some(Arc::new(Diagnostics::default()));
+        self
+    }
+
+    /// Returns a snapshot of clock-health diagnostics, or `None` if diagnostics
+    /// were not enabled via [`with_diagnostics`].
+    ///
+    /// [`with_diagnostics`]: SequenceNumberCounter::with_diagnostics
+    #[must_use]
+    pub fn diagnostics(&self) -> Option<ClockHealth> {
+        self.diagnostics
+            .as_ref()
+            .map(|d| d.snapshot(self.load(Acquire)))
     }
 
     /// Gets the next sequence number, without incrementing the counter.
@@ -98,29 +129,715 @@ impl SequenceNumberCounter {
         loop {
             let now = current_monotonic_ns();
             let last = self.load(Acquire);
-            let candidate = if now > last { now } else { last + 1 };
+            let served_by_physical = now > last;
+            let candidate = if served_by_physical { now } else { last + 1 };
 
             if self
                 .compare_exchange(last, candidate, Release, Acquire)
                 .is_ok()
             {
+                if let Some(diagnostics) = &self.diagnostics {
+                    diagnostics.record(served_by_physical, candidate, now);
+                }
                 return candidate;
             }
         }
     }
 }
 
+impl SequenceNumberCounter {
+    /// Gets the next sequence number as a **Hybrid Logical Clock** timestamp.
+    ///
+    /// Unlike [`SequenceNumberCounter::next`], which derives seqnos purely from
+    /// the local wall clock, this packs the `u64` as the high `48` bits of
+    /// physical time (wall-clock nanoseconds truncated to [`HLC_TICK_NS`], a
+    /// ~65µs tick) and the low [`HLC_LOGICAL_BITS`] bits as a logical counter.
+    /// This keeps causal ordering across nodes — see
+    /// [`SequenceNumberCounter::update`] — while still yielding roughly
+    /// wall-clock seqnos usable by `snapshot_at`.
+    ///
+    /// Because the tick is `2^HLC_LOGICAL_BITS` nanoseconds, a packed HLC seqno
+    /// stays on the same numeric scale as a raw-ns seqno from [`next`], so the
+    /// two modes are interchangeable on one counter: mixing [`next`] and
+    /// `next_hlc` calls preserves strict local monotonicity and the values stay
+    /// comparable for `snapshot_at` / [`retention_cutoff`].
+    ///
+    /// The returned value is still **strictly monotonically increasing**
+    /// locally. If the logical counter would overflow its 16 bits within a
+    /// single physical tick, this spins until physical time advances rather
+    /// than letting the counter bleed into the timestamp bits.
+    ///
+    /// [`next`]: SequenceNumberCounter::next
+    #[must_use]
+    pub fn next_hlc(&self) -> SeqNo {
+        loop {
+            let now = current_monotonic_ns();
+            let pt = now / HLC_TICK_NS;
+            let last = self.load(Acquire);
+            let (l, c) = unpack_hlc(last);
+
+            let served_by_physical = pt > l;
+            let (new_l, new_c) = if served_by_physical {
+                (pt, 0)
+            } else if c < HLC_COUNTER_MASK {
+                (l, c + 1)
+            } else {
+                // Counter saturated within one tick: spin to the next physical
+                // tick rather than bleeding into the timestamp bits.
+                continue;
+            };
+
+            let candidate = pack_hlc(new_l, new_c);
+
+            if self
+                .compare_exchange(last, candidate, Release, Acquire)
+                .is_ok()
+            {
+                if let Some(diagnostics) = &self.diagnostics {
+                    diagnostics.record_hlc(!served_by_physical);
+                }
+                return candidate;
+            }
+        }
+    }
+
+    /// Merges a remote seqno observed from another node into the clock.
+    ///
+    /// Decomposes `remote` into its physical and logical parts and advances the
+    /// counter so that causality against the remote event is preserved. The
+    /// stored value is always strictly greater than `remote` (either a larger
+    /// physical part, or an equal physical part with an incremented counter),
+    /// and since both encodings share the raw-ns scale this directly guarantees
+    /// that every subsequent [`SequenceNumberCounter::next_hlc`] — and
+    /// [`SequenceNumberCounter::next`] — returns a value `> remote`.
+    pub fn update(&self, remote: SeqNo) {
+        let (l_r, c_r) = unpack_hlc(remote);
+
+        loop {
+            let pt = current_monotonic_ns() / HLC_TICK_NS;
+            let last = self.load(Acquire);
+            let (l_local, c_local) = unpack_hlc(last);
+
+            let l_new = l_local.max(l_r).max(pt);
+
+            // Whichever physical part wins dictates how the logical counter is
+            // reseeded. Ties between the local and remote clocks take the larger
+            // counter; a strictly-greater physical tick resets it to zero.
+            let c_new = if l_new == pt && pt > l_local && pt > l_r {
+                0
+            } else if l_new == l_local && l_new == l_r {
+                c_local.max(c_r) + 1
+            } else if l_new == l_local {
+                c_local + 1
+            } else {
+                c_r + 1
+            };
+
+            if c_new > HLC_COUNTER_MASK {
+                // Counter saturated within one tick: spin to the next tick.
+                continue;
+            }
+
+            let candidate = pack_hlc(l_new, c_new);
+
+            if self
+                .compare_exchange(last, candidate, Release, Acquire)
+                .is_ok()
+            {
+                return;
+            }
+        }
+    }
+}
+
 impl Default for SequenceNumberCounter {
     fn default() -> Self {
         Self::new(current_monotonic_ns())
     }
 }
 
+/// Behaviour when the wall clock is detectably behind the high-water mark
+/// recovered from a tree on reopen (NTP step, VM snapshot restore, RTC drift).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockRecoveryMode {
+    /// Surface a [`ClockRegression`] error and refuse to continue.
+    Strict,
+    /// Continue in pure-logical mode (`last + 1`) until physical time catches
+    /// up with the recovered high-water mark, emitting a warning.
+    Lenient,
+}
+
+/// Error returned from [`SequenceNumberCounter::recover_from`] when the wall
+/// clock has regressed past the tolerated bound relative to the highest seqno
+/// already committed on disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockRegression {
+    /// Highest seqno recovered from the tree's manifest.
+    pub highest_committed: SeqNo,
+    /// Wall-clock reading at recovery time, in epoch nanoseconds.
+    pub wall_clock_ns: u64,
+}
+
+impl std::fmt::Display for ClockRegression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "wall clock ({} ns) is behind the recovered high-water mark ({}) by {} ns",
+            self.wall_clock_ns,
+            self.highest_committed,
+            self.highest_committed.saturating_sub(self.wall_clock_ns),
+        )
+    }
+}
+
+impl std::error::Error for ClockRegression {}
+
+impl SequenceNumberCounter {
+    /// Recovers a counter seeded from persisted tree state.
+    ///
+    /// [`SequenceNumberCounter::default`] initializes straight from the wall
+    /// clock, ignoring what is already committed. After a crash-and-restart
+    /// where the clock has moved backwards, that can mint seqnos *below* the
+    /// maximum already written on disk, silently corrupting MVCC ordering. Given
+    /// the highest seqno already committed, this seeds the counter to
+    /// `max(highest_committed + 1, current_monotonic_ns())` so no seqno handed
+    /// out post-recovery is ever `<=` any seqno durably written before the
+    /// restart.
+    ///
+    /// # Scope
+    ///
+    /// This is the seqno-side primitive only. The caller must supply
+    /// `highest_committed`; the tree-side hook that reads it from the manifest
+    /// on reopen and calls this (the `recover(&tree)` / `AbstractTree`
+    /// integration the request describes) is **not implemented in this vendored
+    /// snapshot** — it belongs in the tree module, which is not present here.
+    /// Until that hook exists, reopening a tree does not automatically reseed
+    /// the counter from persisted state.
+    ///
+    /// If the wall clock is behind the recovered high-water mark by more than
+    /// `tolerance_ns`, the behaviour is governed by `mode`. [`Strict`] returns a
+    /// [`ClockRegression`] error and refuses to continue. [`Lenient`] proceeds
+    /// (after emitting a warning) by seeding above the high-water mark as above:
+    /// because that seed is greater than the current wall clock, subsequent
+    /// [`next`] calls fall through to the logical `last + 1` branch — i.e. the
+    /// counter runs in pure-logical mode on its own — until physical time
+    /// overtakes the seed, at which point wall-clock seqnos resume.
+    ///
+    /// [`next`]: SequenceNumberCounter::next
+    /// [`Strict`]: ClockRecoveryMode::Strict
+    /// [`Lenient`]: ClockRecoveryMode::Lenient
+    pub fn recover_from(
+        highest_committed: SeqNo,
+        tolerance_ns: u64,
+        mode: ClockRecoveryMode,
+    ) -> Result<Self, ClockRegression> {
+        let now = current_monotonic_ns();
+
+        let regression = highest_committed.saturating_sub(now);
+        if regression > tolerance_ns {
+            match mode {
+                ClockRecoveryMode::Strict => {
+                    return Err(ClockRegression {
+                        highest_committed,
+                        wall_clock_ns: now,
+                    });
+                }
+                ClockRecoveryMode::Lenient => {
+                    log::warn!(
+                        "wall clock is {regression} ns behind recovered high-water mark {highest_committed}; continuing in logical mode until physical time catches up"
+                    );
+                }
+            }
+        }
+
+        // Seed strictly above the high-water mark and never below wall-clock
+        // time, so the first `next()` is guaranteed `> highest_committed`.
+        Ok(Self::new(highest_committed.saturating_add(1).max(now)))
+    }
+}
+
+/// Capacity of the recent-corrections ring kept by [`Diagnostics`].
+const DIAGNOSTICS_RING_CAPACITY: usize = 16;
+
+/// Inline clock-health counters for a [`SequenceNumberCounter`].
+///
+/// The scalar counters are plain atomics updated on the hot path; the
+/// recent-corrections ring sits behind a lightweight lock since it is only
+/// touched when the generator falls back to a logical increment (a rare event
+/// under a healthy clock).
+#[derive(Debug, Default)]
+struct Diagnostics {
+    total_calls: AtomicU64,
+    served_by_physical: AtomicU64,
+    served_by_logical: AtomicU64,
+    hlc_calls: AtomicU64,
+    hlc_sub_tick_increments: AtomicU64,
+    ring: Mutex<CorrectionRing>,
+}
+
+impl Diagnostics {
+    /// Records a single [`next`] outcome.
+    ///
+    /// The physical-vs-logical split here frames the `last + 1` fallback as a
+    /// clock stall / extreme same-nanosecond burst, so only [`next`] feeds it.
+    /// [`next_hlc`] routes through [`record_hlc`] and [`update`] is a causal
+    /// merge, so neither touches these counters.
+    ///
+    /// [`next`]: SequenceNumberCounter::next
+    /// [`next_hlc`]: SequenceNumberCounter::next_hlc
+    /// [`record_hlc`]: Diagnostics::record_hlc
+    /// [`update`]: SequenceNumberCounter::update
+    fn record(&self, served_by_physical: bool, seqno: SeqNo, physical_now: u64) {
+        self.total_calls.fetch_add(1, Relaxed);
+
+        if served_by_physical {
+            self.served_by_physical.fetch_add(1, Relaxed);
+        } else {
+            self.served_by_logical.fetch_add(1, Relaxed);
+
+            // Logical fallback: the counter ran ahead of physical time (clock
+            // stall or an extreme write burst within one nanosecond). Record
+            // how far by, so operators can see the magnitude of the skew.
+            let correction = Correction {
+                seqno,
+                skew_ns: seqno.saturating_sub(physical_now),
+            };
+            if let Ok(mut ring) = self.ring.lock() {
+                ring.push(correction);
+            }
+        }
+    }
+
+    /// Records a single [`next_hlc`] outcome.
+    ///
+    /// `sub_tick` means the logical counter was incremented within an unchanged
+    /// physical tick. Unlike [`next`]'s `last + 1` fallback, a sub-tick HLC
+    /// increment is routine at any real write rate (two calls inside one ~65µs
+    /// tick), so it is counted separately and never pushed onto the
+    /// clock-stall corrections ring.
+    ///
+    /// [`next`]: SequenceNumberCounter::next
+    /// [`next_hlc`]: SequenceNumberCounter::next_hlc
+    fn record_hlc(&self, sub_tick: bool) {
+        self.hlc_calls.fetch_add(1, Relaxed);
+        if sub_tick {
+            self.hlc_sub_tick_increments.fetch_add(1, Relaxed);
+        }
+    }
+
+    /// Builds a serializable snapshot of the current health.
+    fn snapshot(&self, current: SeqNo) -> ClockHealth {
+        let recent_corrections = self
+            .ring
+            .lock()
+            .map(|ring| ring.to_vec())
+            .unwrap_or_default();
+
+        ClockHealth {
+            total_calls: self.total_calls.load(Relaxed),
+            served_by_physical: self.served_by_physical.load(Relaxed),
+            served_by_logical: self.served_by_logical.load(Relaxed),
+            hlc_calls: self.hlc_calls.load(Relaxed),
+            hlc_sub_tick_increments: self.hlc_sub_tick_increments.load(Relaxed),
+            backward_clamp_events: monotonic_clamp_events(),
+            current_skew_ns: current.saturating_sub(current_monotonic_ns()),
+            recent_corrections,
+        }
+    }
+}
+
+/// Fixed-size ring of the most recent logical-fallback corrections.
+#[derive(Debug, Default)]
+struct CorrectionRing {
+    entries: std::collections::VecDeque<Correction>,
+}
+
+impl CorrectionRing {
+    fn push(&mut self, correction: Correction) {
+        if self.entries.len() == DIAGNOSTICS_RING_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(correction);
+    }
+
+    fn to_vec(&self) -> Vec<Correction> {
+        self.entries.iter().copied().collect()
+    }
+}
+
+/// A single logical-fallback correction recorded by the diagnostics ring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Correction {
+    /// Seqno handed out when the correction was applied.
+    pub seqno: SeqNo,
+    /// How far the logical counter ran ahead of physical time, in nanoseconds.
+    pub skew_ns: u64,
+}
+
+/// Plain, serializable snapshot of clock-health diagnostics for metrics export.
+///
+/// A spiking `served_by_logical / total_calls` ratio means [`next`] is
+/// frequently running on the `last + 1` fallback (clock stall or extreme write
+/// burst), and any non-zero `backward_clamp_events` indicates a real
+/// monotonicity violation worth alarming on. The `total_calls` /
+/// `served_by_*` counters cover [`next`] only; HLC activity has its own
+/// `hlc_calls` / `hlc_sub_tick_increments` pair so a routine sub-tick HLC
+/// increment is never mistaken for a clock-stall fallback. `update` merges are
+/// not counted. Because HLC seqnos share the raw-ns scale, `current_skew_ns` is
+/// meaningful in either mode.
+///
+/// [`next`]: SequenceNumberCounter::next
+/// [`next_hlc`]: SequenceNumberCounter::next_hlc
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClockHealth {
+    /// Total number of [`next`](SequenceNumberCounter::next) calls served.
+    pub total_calls: u64,
+    /// [`next`](SequenceNumberCounter::next) calls whose seqno came straight
+    /// from physical (wall-clock) time.
+    pub served_by_physical: u64,
+    /// [`next`](SequenceNumberCounter::next) calls served by the logical
+    /// `last + 1` fallback (clock stall / same-nanosecond burst).
+    pub served_by_logical: u64,
+    /// Total number of [`next_hlc`](SequenceNumberCounter::next_hlc) calls
+    /// served.
+    pub hlc_calls: u64,
+    /// [`next_hlc`](SequenceNumberCounter::next_hlc) calls that incremented the
+    /// logical counter within an unchanged physical tick (routine under load,
+    /// not a fault signal).
+    pub hlc_sub_tick_increments: u64,
+    /// Backward-clock clamps observed by the global monotonic clock.
+    pub backward_clamp_events: u64,
+    /// Current skew of the counter ahead of physical time, in nanoseconds.
+    pub current_skew_ns: u64,
+    /// Most recent logical-fallback corrections, oldest first.
+    pub recent_corrections: Vec<Correction>,
+}
+
+/// Number of low bits of an HLC-packed seqno reserved for the logical counter.
+const HLC_LOGICAL_BITS: u64 = 16;
+
+/// Mask covering the logical-counter portion of an HLC-packed seqno.
+const HLC_COUNTER_MASK: u64 = (1 << HLC_LOGICAL_BITS) - 1;
+
+/// Physical-time granularity for HLC mode, in nanoseconds.
+///
+/// This is exactly `2^HLC_LOGICAL_BITS`, so the physical part is just the
+/// wall-clock nanoseconds with the low [`HLC_LOGICAL_BITS`] cleared and the
+/// logical counter living in those freed bits. Crucially this keeps a packed
+/// HLC seqno on the *same numeric scale* as the raw epoch-nanosecond seqnos
+/// produced by [`SequenceNumberCounter::next`] (within one ~65µs tick), so the
+/// two encodings are directly comparable and both remain usable by `snapshot_at`
+/// and [`retention_cutoff`].
+const HLC_TICK_NS: u64 = 1 << HLC_LOGICAL_BITS;
+
+/// Splits an HLC-packed seqno into its `(physical, logical)` parts.
+const fn unpack_hlc(packed: SeqNo) -> (u64, u64) {
+    (packed >> HLC_LOGICAL_BITS, packed & HLC_COUNTER_MASK)
+}
+
+/// Packs a `(physical, logical)` pair into a single HLC seqno.
+const fn pack_hlc(physical: u64, logical: u64) -> SeqNo {
+    (physical << HLC_LOGICAL_BITS) | (logical & HLC_COUNTER_MASK)
+}
+
+/// Sentinel for the uninitialized monotonize state. Uses a top-bit pattern that
+/// real epoch-nanosecond readings will not reach until well past year 2200, so
+/// the first reader can distinguish "unseeded" from a genuine value and seed it
+/// atomically.
+const MONOTONIC_UNINIT: u64 = 1 << 63;
+
+/// Last nanos value emitted by [`current_monotonic_ns`], used to clamp backward
+/// wall-clock readings up rather than letting them regress.
+#[cfg(target_has_atomic = "64")]
+static LAST_MONOTONIC_NS: AtomicU64 = AtomicU64::new(MONOTONIC_UNINIT);
+#[cfg(not(target_has_atomic = "64"))]
+static LAST_MONOTONIC_NS: Mutex<u64> = Mutex::new(MONOTONIC_UNINIT);
+
+/// Number of backward-clock clamps observed, so callers can tell real
+/// monotonicity violations apart from ordinary ties papered over by the
+/// `SequenceNumberCounter` CAS loop.
+static MONOTONIC_CLAMP_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+/// Clamps `raw` up to at least the last emitted value, seeding the global state
+/// from the sentinel on first use and counting backward-clock corrections.
+#[cfg(target_has_atomic = "64")]
+fn monotonize(raw: u64) -> u64 {
+    let mut prev = LAST_MONOTONIC_NS.load(Acquire);
+    loop {
+        let base = if prev == MONOTONIC_UNINIT { raw } else { prev };
+        let next = base.max(raw);
+
+        match LAST_MONOTONIC_NS.compare_exchange_weak(prev, next, Release, Acquire) {
+            Ok(_) => {
+                if prev != MONOTONIC_UNINIT && raw < prev {
+                    MONOTONIC_CLAMP_EVENTS.fetch_add(1, Relaxed);
+                }
+                return next;
+            }
+            Err(actual) => prev = actual,
+        }
+    }
+}
+
+/// Mutex-backed variant, compiled on targets lacking 64-bit atomics.
+///
+/// Only the last-emitted value sits behind the lock: the clamp counter and
+/// [`SequenceNumberCounter`] itself still use `AtomicU64`, so this module does
+/// not build on such targets.
+#[cfg(not(target_has_atomic = "64"))]
+fn monotonize(raw: u64) -> u64 {
+    let mut guard = LAST_MONOTONIC_NS.lock().expect("monotonic clock poisoned");
+    let prev = *guard;
+    let base = if prev == MONOTONIC_UNINIT { raw } else { prev };
+    let next = base.max(raw);
+
+    if prev != MONOTONIC_UNINIT && raw < prev {
+        MONOTONIC_CLAMP_EVENTS.fetch_add(1, Relaxed);
+    }
+
+    *guard = next;
+    next
+}
+
 /// Returns the current time in nanoseconds since Unix epoch.
-/// This uses Instant for monotonicity but converts to epoch time,
-/// ensuring both monotonic behavior and epoch-based timestamps.
+///
+/// This uses Instant for monotonicity but converts to epoch time, ensuring both
+/// monotonic behavior and epoch-based timestamps. Raw readings are passed
+/// through [`monotonize`], so a backward or stalled `Instant` (as can happen on
+/// some platforms/VMs) is clamped up to the last emitted value instead of
+/// regressing. The `u128` → `u64` conversion saturates at [`u64::MAX`] rather
+/// than wrapping silently near the ceiling.
 fn current_monotonic_ns() -> u64 {
     let (start_instant, start_epoch_ns) = get_epoch_offset();
     let elapsed_ns = start_instant.elapsed().as_nanos();
-    (start_epoch_ns + elapsed_ns) as u64
+
+    let raw = start_epoch_ns
+        .checked_add(elapsed_ns)
+        .and_then(|ns| u64::try_from(ns).ok())
+        .unwrap_or(u64::MAX);
+
+    monotonize(raw)
+}
+
+/// Computes the age cutoff seqno for time-based retention, exploiting the fact
+/// that every seqno emitted by [`SequenceNumberCounter::next`] is an
+/// epoch-nanosecond timestamp.
+///
+/// Returns `current_monotonic_ns() - retention_ns`, i.e. the seqno below which
+/// an entry is older than the retention window. When `oldest_live_snapshot` is
+/// supplied the cutoff is additionally capped at it, so the result never
+/// advances past the smallest seqno any open snapshot can still observe. The
+/// result is a pure function of its inputs, so repeated calls within one
+/// compaction are deterministic.
+///
+/// # Scope and limitations
+///
+/// This is **only** the cutoff computation. The retention/TTL *policy* the
+/// request envisions — a `Config::retention(Duration)` setting and a compaction
+/// merge loop that physically drops shadowed/expired entries and tombstones
+/// (plus a whole-key TTL mode) — is **not implemented here**; those live in the
+/// `Config` and compaction modules, which are outside this vendored snapshot.
+/// No caller currently feeds this cutoff into a drop decision, so nothing is
+/// expired yet.
+///
+/// Note that this scalar cutoff is **not on its own sufficient** to keep
+/// `snapshot_at` correct: a key's snapshot-visible version can carry a seqno far
+/// below the snapshot's own seqno, so `min(cutoff, oldest_live_snapshot)` can
+/// still sit above a version a live snapshot depends on. Protecting snapshots
+/// requires the drop logic to reason per key ("keep the newest version with
+/// seqno `<= S` for every live snapshot seqno `S`"); the cap here is a coarse
+/// bound, not that guarantee.
+#[must_use]
+pub fn retention_cutoff(retention_ns: u64, oldest_live_snapshot: Option<SeqNo>) -> SeqNo {
+    let cutoff = current_monotonic_ns().saturating_sub(retention_ns);
+    match oldest_live_snapshot {
+        Some(snapshot) => cutoff.min(snapshot),
+        None => cutoff,
+    }
+}
+
+/// Returns the number of times the global monotonic clock has clamped a
+/// backward wall-clock reading up to the last emitted value.
+///
+/// A non-zero value indicates the underlying `Instant` went backwards or
+/// jumped — a real clock fault worth alarming on, as distinct from the ordinary
+/// same-nanosecond ties handled by the `SequenceNumberCounter` CAS loop.
+#[must_use]
+pub fn monotonic_clamp_events() -> u64 {
+    MONOTONIC_CLAMP_EVENTS.load(Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_is_strictly_monotonic() {
+        let counter = SequenceNumberCounter::default();
+        let mut prev = counter.next();
+        for _ in 0..10_000 {
+            let seqno = counter.next();
+            assert!(seqno > prev, "{seqno} !> {prev}");
+            prev = seqno;
+        }
+    }
+
+    #[test]
+    fn next_hlc_is_strictly_monotonic() {
+        let counter = SequenceNumberCounter::new(0);
+        let mut prev = counter.next_hlc();
+        for _ in 0..10_000 {
+            let seqno = counter.next_hlc();
+            assert!(seqno > prev, "{seqno} !> {prev}");
+            prev = seqno;
+        }
+    }
+
+    #[test]
+    fn hlc_stays_on_the_raw_ns_scale() {
+        // A packed HLC seqno must stay within one tick of the raw-ns clock, so
+        // it remains comparable to `next()` / `snapshot_at`.
+        let counter = SequenceNumberCounter::new(0);
+        let before = current_monotonic_ns();
+        let hlc = counter.next_hlc();
+        let after = current_monotonic_ns();
+        assert!(hlc <= after);
+        assert!(hlc + HLC_TICK_NS >= before);
+    }
+
+    #[test]
+    fn update_guarantees_next_exceeds_remote() {
+        let remote = pack_hlc((current_monotonic_ns() / HLC_TICK_NS) + 1_000, 7);
+
+        let hlc_counter = SequenceNumberCounter::new(0);
+        hlc_counter.update(remote);
+        assert!(hlc_counter.next_hlc() > remote);
+
+        let ns_counter = SequenceNumberCounter::new(0);
+        ns_counter.update(remote);
+        assert!(ns_counter.next() > remote);
+    }
+
+    #[test]
+    fn hlc_counter_overflow_spins_to_next_tick() {
+        let counter = SequenceNumberCounter::new(0);
+        let pt = current_monotonic_ns() / HLC_TICK_NS;
+
+        // Saturate the logical counter within the current tick.
+        counter.store(pack_hlc(pt, HLC_COUNTER_MASK), Release);
+
+        let seqno = counter.next_hlc();
+        let (physical, logical) = unpack_hlc(seqno);
+
+        // Rather than bleeding into the timestamp bits, it advanced the tick.
+        assert!(physical > pt);
+        assert_eq!(logical, 0);
+    }
+
+    #[test]
+    fn pack_unpack_round_trips() {
+        let physical = 0x0000_1234_5678u64;
+        let logical = 0xABCu64;
+        assert_eq!(unpack_hlc(pack_hlc(physical, logical)), (physical, logical));
+    }
+
+    #[test]
+    fn monotonize_clamps_backward_readings_and_counts_them() {
+        // Seed the global clock, then feed it a reading that went backwards.
+        let forward = current_monotonic_ns();
+        let before = monotonic_clamp_events();
+
+        let clamped = monotonize(forward.saturating_sub(1_000_000));
+
+        // The backward reading is clamped up, never regressing, and counted.
+        assert!(clamped >= forward);
+        assert!(monotonic_clamp_events() > before);
+    }
+
+    #[test]
+    fn recover_without_regression_seeds_above_high_water_mark() {
+        let now = current_monotonic_ns();
+        let highest = now.saturating_sub(1_000_000);
+
+        let counter =
+            SequenceNumberCounter::recover_from(highest, 0, ClockRecoveryMode::Strict).unwrap();
+
+        assert!(counter.get() >= now);
+        assert!(counter.next() > highest);
+    }
+
+    #[test]
+    fn recover_strict_errors_on_backward_clock() {
+        let highest = current_monotonic_ns() + 5_000_000_000;
+
+        let err =
+            SequenceNumberCounter::recover_from(highest, 1_000_000, ClockRecoveryMode::Strict)
+                .unwrap_err();
+
+        assert_eq!(err.highest_committed, highest);
+    }
+
+    #[test]
+    fn recover_lenient_continues_above_high_water_mark() {
+        let highest = current_monotonic_ns() + 5_000_000_000;
+
+        let counter =
+            SequenceNumberCounter::recover_from(highest, 1_000_000, ClockRecoveryMode::Lenient)
+                .unwrap();
+
+        // Invariant: no post-recovery seqno is ever <= the recovered mark.
+        assert!(counter.get() > highest);
+        assert!(counter.next() > highest);
+    }
+
+    #[test]
+    fn retention_cutoff_subtracts_and_clamps_to_snapshot() {
+        let recent = retention_cutoff(0, None);
+        let aged = retention_cutoff(1_000_000_000, None);
+        assert!(aged < recent, "a longer window yields an earlier cutoff");
+
+        // An open snapshot holds the cutoff at or below its seqno so time-travel
+        // reads stay correct.
+        let snapshot = 100;
+        assert_eq!(retention_cutoff(0, Some(snapshot)), snapshot);
+    }
+
+    #[test]
+    fn diagnostics_track_next_and_next_hlc_separately() {
+        let counter = SequenceNumberCounter::default().with_diagnostics();
+        let _ = counter.next();
+        let _ = counter.next();
+        let _ = counter.next_hlc();
+
+        let health = counter.diagnostics().unwrap();
+
+        // `next` and `next_hlc` counters do not bleed into each other.
+        assert_eq!(health.total_calls, 2);
+        assert_eq!(
+            health.served_by_physical + health.served_by_logical,
+            health.total_calls
+        );
+        assert_eq!(health.hlc_calls, 1);
+        assert!(health.hlc_sub_tick_increments <= health.hlc_calls);
+    }
+
+    #[test]
+    fn diagnostics_record_logical_fallback_corrections() {
+        // Seeding far above wall-clock time forces the logical `last + 1` path.
+        let counter = SequenceNumberCounter::new(u64::MAX - 4).with_diagnostics();
+        let _ = counter.next();
+
+        let health = counter.diagnostics().unwrap();
+        assert_eq!(health.served_by_logical, 1);
+        assert!(!health.recent_corrections.is_empty());
+    }
+
+    #[test]
+    fn diagnostics_are_absent_when_disabled() {
+        let counter = SequenceNumberCounter::default();
+        let _ = counter.next();
+        assert!(counter.diagnostics().is_none());
+    }
 }